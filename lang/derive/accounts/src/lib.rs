@@ -0,0 +1,27 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse_macro_input;
+
+#[proc_macro_derive(Accounts, attributes(account))]
+pub fn derive_accounts(item: TokenStream) -> TokenStream {
+    let strct = parse_macro_input!(item as syn::ItemStruct);
+
+    // `parse` returns a spanned `syn::Error` instead of panicking, so a
+    // malformed `#[account(...)]` attribute becomes a normal compiler
+    // diagnostic rather than an ICE-style proc-macro panic.
+    if let Err(e) = anchor_syn::parser::accounts::parse(&strct) {
+        return e.to_compile_error().into();
+    }
+
+    // Per-field account deserialization codegen (building `Self` from the
+    // raw `AccountInfo` slice) is not implemented yet. Fail at compile time
+    // instead of emitting a `try_accounts` impl that would panic the first
+    // time any caller's program actually invoked it.
+    TokenStream::from(quote! {
+        compile_error!(
+            "#[derive(Accounts)] is not fully implemented: per-field account deserialization is not yet generated by this crate"
+        );
+    })
+}
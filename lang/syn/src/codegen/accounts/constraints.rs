@@ -0,0 +1,160 @@
+use crate::{
+    Constraint, ConstraintAddress, ConstraintAssociated, ConstraintBelongsTo, ConstraintExecutable,
+    ConstraintLiteral, ConstraintOwner, ConstraintRentExempt, ConstraintSeeds, ConstraintSigner,
+    ConstraintState, Field,
+};
+use quote::quote;
+
+pub fn generate(f: &Field) -> proc_macro2::TokenStream {
+    let checks: Vec<proc_macro2::TokenStream> = f
+        .constraints
+        .iter()
+        .map(|c| generate_constraint(f, c))
+        .collect();
+    quote! {
+        #(#checks)*
+    }
+}
+
+fn generate_constraint(f: &Field, c: &Constraint) -> proc_macro2::TokenStream {
+    match c {
+        Constraint::BelongsTo(c) => generate_constraint_belongs_to(f, c),
+        Constraint::Signer(c) => generate_constraint_signer(f, c),
+        Constraint::Literal(c) => generate_constraint_literal(c),
+        Constraint::Owner(c) => generate_constraint_owner(f, c),
+        Constraint::Address(c) => generate_constraint_address(f, c),
+        Constraint::RentExempt(c) => generate_constraint_rent_exempt(f, c),
+        Constraint::Seeds(c) => generate_constraint_seeds(f, c),
+        Constraint::Executable(c) => generate_constraint_executable(f, c),
+        Constraint::State(c) => generate_constraint_state(f, c),
+        Constraint::Associated(c) => generate_constraint_associated(f, c),
+    }
+}
+
+fn generate_constraint_belongs_to(f: &Field, c: &ConstraintBelongsTo) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    let target = &c.join_target;
+    quote! {
+        if &#ident.#target != #target.to_account_info().key {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+fn generate_constraint_signer(f: &Field, _c: &ConstraintSigner) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    quote! {
+        if !#ident.to_account_info().is_signer {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::MissingRequiredSignature);
+        }
+    }
+}
+
+fn generate_constraint_literal(c: &ConstraintLiteral) -> proc_macro2::TokenStream {
+    let tokens = &c.tokens;
+    quote! {
+        if !(#tokens) {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+// Emits `if #key_expr != <the literal pubkey> { return Err(...) }`, shared
+// by the literal arms of both `owner` and `address` constraint codegen.
+fn pubkey_literal_eq(
+    key_expr: proc_macro2::TokenStream,
+    address: &[u8; 32],
+) -> proc_macro2::TokenStream {
+    let bytes = address.iter();
+    quote! {
+        if #key_expr != &anchor_lang::solana_program::pubkey::Pubkey::new_from_array([#(#bytes),*]) {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+fn generate_constraint_owner(f: &Field, c: &ConstraintOwner) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    match c {
+        ConstraintOwner::Field(target) => quote! {
+            if #ident.to_account_info().owner != #target.to_account_info().key {
+                return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+            }
+        },
+        ConstraintOwner::Address(address) => {
+            pubkey_literal_eq(quote! { #ident.to_account_info().owner }, address)
+        }
+    }
+}
+
+fn generate_constraint_address(f: &Field, c: &ConstraintAddress) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    match c {
+        ConstraintAddress::Field(target) => quote! {
+            if #ident.to_account_info().key != #target.to_account_info().key {
+                return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+            }
+        },
+        ConstraintAddress::Literal(address) => {
+            pubkey_literal_eq(quote! { #ident.to_account_info().key }, address)
+        }
+    }
+}
+
+fn generate_constraint_rent_exempt(
+    f: &Field,
+    c: &ConstraintRentExempt,
+) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    match c {
+        ConstraintRentExempt::Skip => quote! {},
+        ConstraintRentExempt::Enforce => quote! {
+            if !rent.is_exempt(#ident.to_account_info().lamports(), #ident.to_account_info().data_len()) {
+                return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+            }
+        },
+    }
+}
+
+fn generate_constraint_seeds(f: &Field, c: &ConstraintSeeds) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    let seeds = &c.seeds;
+    quote! {
+        let (__pda_address, _bump) =
+            anchor_lang::solana_program::pubkey::Pubkey::find_program_address(&#seeds, program_id);
+        if #ident.to_account_info().key != &__pda_address {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+fn generate_constraint_executable(
+    f: &Field,
+    _c: &ConstraintExecutable,
+) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    quote! {
+        if !#ident.to_account_info().executable {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+fn generate_constraint_state(f: &Field, c: &ConstraintState) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    let program_target = &c.program_target;
+    quote! {
+        if #ident.to_account_info().owner != #program_target.to_account_info().key {
+            return Err(anchor_lang::solana_program::program_error::ProgramError::Custom(1)); // TODO: proper error code.
+        }
+    }
+}
+
+// TODO: deriving the associated PDA from `associated`/`with` seeds is not
+// yet implemented here; this only reserves the match arm.
+fn generate_constraint_associated(
+    _f: &Field,
+    _c: &ConstraintAssociated,
+) -> proc_macro2::TokenStream {
+    quote! {}
+}
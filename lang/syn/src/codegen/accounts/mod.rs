@@ -0,0 +1,64 @@
+pub mod constraints;
+
+use crate::{AccountField, AccountsStruct, Ty};
+use quote::quote;
+
+pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let checks: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|af| match af {
+            AccountField::Field(f) => constraints::generate(f),
+            // Composite (nested) accounts structs get no checks here: neither
+            // the nested struct's fields nor the constraints written on the
+            // composite field itself are emitted by this function.
+            AccountField::AccountsStruct(_) => quote! {},
+        })
+        .collect();
+    quote! {
+        #(#checks)*
+    }
+}
+
+// Renders the Rust type a field's account should be deserialized into,
+// e.g. `anchor_lang::ProgramAccount<'info, MyAccount>`.
+pub fn generate_field_ty(ty: &Ty) -> proc_macro2::TokenStream {
+    match ty {
+        Ty::AccountInfo => quote! { anchor_lang::solana_program::account_info::AccountInfo<'info> },
+        Ty::ProgramState(ty) => {
+            let account_ident = &ty.account_ident;
+            quote! { anchor_lang::ProgramState<'info, #account_ident> }
+        }
+        Ty::CpiState(ty) => {
+            let account_ident = &ty.account_ident;
+            quote! { anchor_lang::CpiState<'info, #account_ident> }
+        }
+        Ty::ProgramAccount(ty) => {
+            let account_ident = &ty.account_ident;
+            quote! { anchor_lang::ProgramAccount<'info, #account_ident> }
+        }
+        Ty::CpiAccount(ty) => {
+            let account_ident = &ty.account_ident;
+            quote! { anchor_lang::CpiAccount<'info, #account_ident> }
+        }
+        Ty::Loader(ty) => {
+            let account_ident = &ty.account_ident;
+            quote! { anchor_lang::Loader<'info, #account_ident> }
+        }
+        Ty::Sysvar(ty) => {
+            let account_ident = match ty {
+                crate::SysvarTy::Clock => quote! { Clock },
+                crate::SysvarTy::Rent => quote! { Rent },
+                crate::SysvarTy::EpochSchedule => quote! { EpochSchedule },
+                crate::SysvarTy::Fees => quote! { Fees },
+                crate::SysvarTy::RecentBlockhashes => quote! { RecentBlockhashes },
+                crate::SysvarTy::SlotHashes => quote! { SlotHashes },
+                crate::SysvarTy::SlotHistory => quote! { SlotHistory },
+                crate::SysvarTy::StakeHistory => quote! { StakeHistory },
+                crate::SysvarTy::Instructions => quote! { Instructions },
+                crate::SysvarTy::Rewards => quote! { Rewards },
+            };
+            quote! { anchor_lang::Sysvar<'info, #account_ident> }
+        }
+    }
+}
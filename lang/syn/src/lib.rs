@@ -0,0 +1,165 @@
+pub mod codegen;
+pub mod parser;
+
+#[derive(Debug)]
+pub struct AccountsStruct {
+    pub strct: syn::ItemStruct,
+    pub fields: Vec<AccountField>,
+}
+
+impl AccountsStruct {
+    pub fn new(strct: syn::ItemStruct, fields: Vec<AccountField>) -> Self {
+        Self { strct, fields }
+    }
+
+    pub fn ident(&self) -> &syn::Ident {
+        &self.strct.ident
+    }
+}
+
+#[derive(Debug)]
+pub enum AccountField {
+    Field(Field),
+    AccountsStruct(CompositeField),
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub ident: syn::Ident,
+    pub ty: Ty,
+    pub constraints: Vec<Constraint>,
+    pub is_mut: bool,
+    pub is_signer: bool,
+    pub is_init: bool,
+    pub payer: Option<syn::Ident>,
+    pub space: Option<proc_macro2::TokenStream>,
+    pub associated_seeds: Vec<syn::Ident>,
+}
+
+#[derive(Debug)]
+pub struct CompositeField {
+    pub ident: syn::Ident,
+    pub symbol: String,
+    pub constraints: Vec<Constraint>,
+    pub raw_field: syn::Field,
+}
+
+#[derive(Debug)]
+pub enum Ty {
+    AccountInfo,
+    ProgramState(ProgramStateTy),
+    CpiState(CpiStateTy),
+    ProgramAccount(ProgramAccountTy),
+    CpiAccount(CpiAccountTy),
+    Sysvar(SysvarTy),
+    Loader(LoaderTy),
+}
+
+// `account_ident` holds the full, possibly-segmented path of the inner
+// account type (e.g. `crate::state::MyAccount`) rather than a bare
+// `syn::Ident`, so account structs can live outside the crate root.
+#[derive(Debug)]
+pub struct ProgramStateTy {
+    pub account_ident: syn::Path,
+}
+
+#[derive(Debug)]
+pub struct CpiStateTy {
+    pub account_ident: syn::Path,
+}
+
+#[derive(Debug)]
+pub struct ProgramAccountTy {
+    pub account_ident: syn::Path,
+}
+
+#[derive(Debug)]
+pub struct CpiAccountTy {
+    pub account_ident: syn::Path,
+}
+
+#[derive(Debug)]
+pub struct LoaderTy {
+    pub account_ident: syn::Path,
+}
+
+#[derive(Debug)]
+pub enum SysvarTy {
+    Clock,
+    Rent,
+    EpochSchedule,
+    Fees,
+    RecentBlockhashes,
+    SlotHashes,
+    SlotHistory,
+    StakeHistory,
+    Instructions,
+    Rewards,
+}
+
+#[derive(Debug)]
+pub enum Constraint {
+    BelongsTo(ConstraintBelongsTo),
+    Signer(ConstraintSigner),
+    Literal(ConstraintLiteral),
+    Owner(ConstraintOwner),
+    Address(ConstraintAddress),
+    RentExempt(ConstraintRentExempt),
+    Seeds(ConstraintSeeds),
+    Executable(ConstraintExecutable),
+    State(ConstraintState),
+    Associated(ConstraintAssociated),
+}
+
+#[derive(Debug)]
+pub struct ConstraintBelongsTo {
+    pub join_target: syn::Ident,
+}
+
+#[derive(Debug)]
+pub struct ConstraintSigner {}
+
+#[derive(Debug)]
+pub struct ConstraintLiteral {
+    pub tokens: proc_macro2::TokenStream,
+}
+
+// An `owner = ...` constraint: either the key of another field in the
+// accounts struct, or a base58 pubkey baked in at compile time.
+#[derive(Debug)]
+pub enum ConstraintOwner {
+    Field(syn::Ident),
+    Address([u8; 32]),
+}
+
+// An `address = ...` constraint pinning the account's own key, either to
+// another field in the accounts struct or to a base58 pubkey literal.
+#[derive(Debug)]
+pub enum ConstraintAddress {
+    Field(syn::Ident),
+    Literal([u8; 32]),
+}
+
+#[derive(Debug)]
+pub enum ConstraintRentExempt {
+    Skip,
+    Enforce,
+}
+
+#[derive(Debug)]
+pub struct ConstraintSeeds {
+    pub seeds: proc_macro2::Group,
+}
+
+#[derive(Debug)]
+pub struct ConstraintExecutable {}
+
+#[derive(Debug)]
+pub struct ConstraintState {
+    pub program_target: syn::Ident,
+}
+
+#[derive(Debug)]
+pub struct ConstraintAssociated {
+    pub associated_target: syn::Ident,
+}
@@ -1,24 +1,38 @@
 use crate::{
-    AccountField, AccountsStruct, CompositeField, Constraint, ConstraintAssociated,
-    ConstraintBelongsTo, ConstraintExecutable, ConstraintLiteral, ConstraintOwner,
-    ConstraintRentExempt, ConstraintSeeds, ConstraintSigner, ConstraintState, CpiAccountTy,
-    CpiStateTy, Field, LoaderTy, ProgramAccountTy, ProgramStateTy, SysvarTy, Ty,
+    AccountField, AccountsStruct, CompositeField, Constraint, ConstraintAddress,
+    ConstraintAssociated, ConstraintBelongsTo, ConstraintExecutable, ConstraintLiteral,
+    ConstraintOwner, ConstraintRentExempt, ConstraintSeeds, ConstraintSigner, ConstraintState,
+    CpiAccountTy, CpiStateTy, Field, LoaderTy, ProgramAccountTy, ProgramStateTy, SysvarTy, Ty,
 };
 
-pub fn parse(strct: &syn::ItemStruct) -> AccountsStruct {
+/// Result type used throughout this module. Every failure carries the span
+/// of the offending token so the compiler can point directly at it instead
+/// of surfacing a bare panic message.
+type ParseResult<T> = syn::Result<T>;
+
+pub fn parse(strct: &syn::ItemStruct) -> ParseResult<AccountsStruct> {
     let fields = match &strct.fields {
-        syn::Fields::Named(fields) => fields.named.iter().map(parse_account_field).collect(),
-        _ => panic!("invalid input"),
+        syn::Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(parse_account_field)
+            .collect::<ParseResult<Vec<AccountField>>>()?,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                strct,
+                "accounts must be a struct with named fields",
+            ))
+        }
     };
-    AccountsStruct::new(strct.clone(), fields)
+    Ok(AccountsStruct::new(strct.clone(), fields))
 }
 
-fn parse_account_field(f: &syn::Field) -> AccountField {
-    let anchor_attr = parse_account_attr(f);
+fn parse_account_field(f: &syn::Field) -> ParseResult<AccountField> {
+    let anchor_attr = parse_account_attr(f)?;
     parse_field(f, anchor_attr)
 }
 
-fn parse_account_attr(f: &syn::Field) -> Option<&syn::Attribute> {
+fn parse_account_attr(f: &syn::Field) -> ParseResult<Option<&syn::Attribute>> {
     let anchor_attrs: Vec<&syn::Attribute> = f
         .attrs
         .iter()
@@ -33,22 +47,25 @@ fn parse_account_attr(f: &syn::Field) -> Option<&syn::Attribute> {
         })
         .collect();
     match anchor_attrs.len() {
-        0 => None,
-        1 => Some(anchor_attrs[0]),
-        _ => panic!("Invalid syntax: please specify one account attribute."),
+        0 => Ok(None),
+        1 => Ok(Some(anchor_attrs[0])),
+        _ => Err(syn::Error::new_spanned(
+            anchor_attrs[1],
+            "please specify one account attribute",
+        )),
     }
 }
 
-fn parse_field(f: &syn::Field, anchor: Option<&syn::Attribute>) -> AccountField {
+fn parse_field(f: &syn::Field, anchor: Option<&syn::Attribute>) -> ParseResult<AccountField> {
     let ident = f.ident.clone().unwrap();
     let (constraints, is_mut, is_signer, is_init, payer, space, associated_seeds) = match anchor {
         None => (vec![], false, false, false, None, None, Vec::new()),
-        Some(anchor) => parse_constraints(anchor),
+        Some(anchor) => parse_constraints(anchor)?,
     };
-    match is_field_primitive(f) {
+    match is_field_primitive(f)? {
         true => {
-            let ty = parse_ty(f);
-            AccountField::Field(Field {
+            let ty = parse_ty(f)?;
+            Ok(AccountField::Field(Field {
                 ident,
                 ty,
                 constraints,
@@ -58,134 +75,315 @@ fn parse_field(f: &syn::Field, anchor: Option<&syn::Attribute>) -> AccountField
                 payer,
                 space,
                 associated_seeds,
-            })
+            }))
         }
-        false => AccountField::AccountsStruct(CompositeField {
+        false => Ok(AccountField::AccountsStruct(CompositeField {
             ident,
-            symbol: ident_string(f),
+            symbol: ident_string(f)?,
             constraints,
             raw_field: f.clone(),
-        }),
+        })),
     }
 }
 
-fn is_field_primitive(f: &syn::Field) -> bool {
-    match ident_string(f).as_str() {
-        "ProgramState" | "ProgramAccount" | "CpiAccount" | "Sysvar" | "AccountInfo"
-        | "CpiState" | "Loader" => true,
-        _ => false,
-    }
+fn is_field_primitive(f: &syn::Field) -> ParseResult<bool> {
+    Ok(matches!(
+        ident_string(f)?.as_str(),
+        "ProgramState"
+            | "ProgramAccount"
+            | "CpiAccount"
+            | "Sysvar"
+            | "AccountInfo"
+            | "CpiState"
+            | "Loader"
+    ))
 }
 
-fn parse_ty(f: &syn::Field) -> Ty {
+fn parse_ty(f: &syn::Field) -> ParseResult<Ty> {
     let path = match &f.ty {
         syn::Type::Path(ty_path) => ty_path.path.clone(),
-        _ => panic!("invalid account syntax"),
+        _ => return Err(syn::Error::new_spanned(&f.ty, "invalid account type")),
     };
-    match ident_string(f).as_str() {
-        "ProgramState" => Ty::ProgramState(parse_program_state(&path)),
-        "CpiState" => Ty::CpiState(parse_cpi_state(&path)),
-        "ProgramAccount" => Ty::ProgramAccount(parse_program_account(&path)),
-        "CpiAccount" => Ty::CpiAccount(parse_cpi_account(&path)),
-        "Sysvar" => Ty::Sysvar(parse_sysvar(&path)),
+    let ty = match ident_string(f)?.as_str() {
+        "ProgramState" => Ty::ProgramState(parse_program_state(&path)?),
+        "CpiState" => Ty::CpiState(parse_cpi_state(&path)?),
+        "ProgramAccount" => Ty::ProgramAccount(parse_program_account(&path)?),
+        "CpiAccount" => Ty::CpiAccount(parse_cpi_account(&path)?),
+        "Sysvar" => Ty::Sysvar(parse_sysvar(&path)?),
         "AccountInfo" => Ty::AccountInfo,
-        "Loader" => Ty::Loader(parse_program_account_zero_copy(&path)),
-        _ => panic!("invalid account type"),
-    }
+        "Loader" => Ty::Loader(parse_program_account_zero_copy(&path)?),
+        _ => return Err(syn::Error::new_spanned(&f.ty, "invalid account type")),
+    };
+    Ok(ty)
 }
 
-fn ident_string(f: &syn::Field) -> String {
+fn ident_string(f: &syn::Field) -> ParseResult<String> {
     let path = match &f.ty {
         syn::Type::Path(ty_path) => ty_path.path.clone(),
-        _ => panic!("invalid account syntax"),
+        _ => return Err(syn::Error::new_spanned(&f.ty, "invalid account syntax")),
     };
-    // TODO: allow segmented paths.
-    assert!(path.segments.len() == 1);
-    let segments = &path.segments[0];
-    segments.ident.to_string()
+    // The wrapper type (e.g. `ProgramAccount`) is resolved by its last
+    // segment so fully-qualified paths like `anchor_lang::ProgramAccount`
+    // still dispatch correctly.
+    let segment = path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(&path, "invalid account syntax"))?;
+    Ok(segment.ident.to_string())
 }
 
-fn parse_program_state(path: &syn::Path) -> ProgramStateTy {
-    let account_ident = parse_account(&path);
-    ProgramStateTy { account_ident }
+fn parse_program_state(path: &syn::Path) -> ParseResult<ProgramStateTy> {
+    let account_ident = parse_account(path)?;
+    Ok(ProgramStateTy { account_ident })
 }
 
-fn parse_cpi_state(path: &syn::Path) -> CpiStateTy {
-    let account_ident = parse_account(&path);
-    CpiStateTy { account_ident }
+fn parse_cpi_state(path: &syn::Path) -> ParseResult<CpiStateTy> {
+    let account_ident = parse_account(path)?;
+    Ok(CpiStateTy { account_ident })
 }
 
-fn parse_cpi_account(path: &syn::Path) -> CpiAccountTy {
-    let account_ident = parse_account(path);
-    CpiAccountTy { account_ident }
+fn parse_cpi_account(path: &syn::Path) -> ParseResult<CpiAccountTy> {
+    let account_ident = parse_account(path)?;
+    Ok(CpiAccountTy { account_ident })
 }
 
-fn parse_program_account(path: &syn::Path) -> ProgramAccountTy {
-    let account_ident = parse_account(path);
-    ProgramAccountTy { account_ident }
+fn parse_program_account(path: &syn::Path) -> ParseResult<ProgramAccountTy> {
+    let account_ident = parse_account(path)?;
+    Ok(ProgramAccountTy { account_ident })
 }
 
-fn parse_program_account_zero_copy(path: &syn::Path) -> LoaderTy {
-    let account_ident = parse_account(path);
-    LoaderTy { account_ident }
+fn parse_program_account_zero_copy(path: &syn::Path) -> ParseResult<LoaderTy> {
+    let account_ident = parse_account(path)?;
+    Ok(LoaderTy { account_ident })
 }
 
-fn parse_account(path: &syn::Path) -> syn::Ident {
-    let segments = &path.segments[0];
-    match &segments.arguments {
+/// Returns the full, possibly-segmented path of the inner account type,
+/// e.g. `crate::state::MyAccount` in `ProgramAccount<'info, crate::state::MyAccount>`.
+fn parse_account(path: &syn::Path) -> ParseResult<syn::Path> {
+    let segment = path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(path, "invalid account syntax"))?;
+    match &segment.arguments {
         syn::PathArguments::AngleBracketed(args) => {
             // Expected: <'info, MyType>.
-            assert!(args.args.len() == 2);
+            if args.args.len() != 2 {
+                return Err(syn::Error::new_spanned(
+                    args,
+                    "expected angle brackets with a lifetime and a single type, e.g. <'info, MyType>",
+                ));
+            }
             match &args.args[1] {
-                syn::GenericArgument::Type(syn::Type::Path(ty_path)) => {
-                    // TODO: allow segmented paths.
-                    assert!(ty_path.path.segments.len() == 1);
-                    let path_segment = &ty_path.path.segments[0];
-                    path_segment.ident.clone()
-                }
-                _ => panic!("Invalid ProgramAccount"),
+                syn::GenericArgument::Type(syn::Type::Path(ty_path)) => Ok(ty_path.path.clone()),
+                arg => Err(syn::Error::new_spanned(arg, "invalid account type")),
             }
         }
-        _ => panic!("Invalid ProgramAccount"),
+        arguments => Err(syn::Error::new_spanned(
+            arguments,
+            "expected angle-bracketed generic arguments, e.g. <'info, MyType>",
+        )),
     }
 }
 
-fn parse_sysvar(path: &syn::Path) -> SysvarTy {
-    let segments = &path.segments[0];
-    let account_ident = match &segments.arguments {
+fn parse_sysvar(path: &syn::Path) -> ParseResult<SysvarTy> {
+    let segment = path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(path, "invalid account syntax"))?;
+    let account_path = match &segment.arguments {
         syn::PathArguments::AngleBracketed(args) => {
             // Expected: <'info, MyType>.
-            assert!(args.args.len() == 2);
+            if args.args.len() != 2 {
+                return Err(syn::Error::new_spanned(
+                    args,
+                    "expected angle brackets with a lifetime and a single type, e.g. <'info, Clock>",
+                ));
+            }
             match &args.args[1] {
-                syn::GenericArgument::Type(syn::Type::Path(ty_path)) => {
-                    // TODO: allow segmented paths.
-                    assert!(ty_path.path.segments.len() == 1);
-                    let path_segment = &ty_path.path.segments[0];
-                    path_segment.ident.clone()
-                }
-                _ => panic!("Invalid Sysvar"),
+                syn::GenericArgument::Type(syn::Type::Path(ty_path)) => ty_path.path.clone(),
+                arg => return Err(syn::Error::new_spanned(arg, "invalid Sysvar")),
             }
         }
-        _ => panic!("Invalid Sysvar"),
+        arguments => {
+            return Err(syn::Error::new_spanned(
+                arguments,
+                "expected angle-bracketed generic arguments, e.g. <'info, Clock>",
+            ))
+        }
     };
+    let account_ident = &account_path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(&account_path, "invalid Sysvar"))?
+        .ident;
     match account_ident.to_string().as_str() {
-        "Clock" => SysvarTy::Clock,
-        "Rent" => SysvarTy::Rent,
-        "EpochSchedule" => SysvarTy::EpochSchedule,
-        "Fees" => SysvarTy::Fees,
-        "RecentBlockhashes" => SysvarTy::RecentBlockhashes,
-        "SlotHashes" => SysvarTy::SlotHashes,
-        "SlotHistory" => SysvarTy::SlotHistory,
-        "StakeHistory" => SysvarTy::StakeHistory,
-        "Instructions" => SysvarTy::Instructions,
-        "Rewards" => SysvarTy::Rewards,
-        _ => panic!("Invalid Sysvar"),
+        "Clock" => Ok(SysvarTy::Clock),
+        "Rent" => Ok(SysvarTy::Rent),
+        "EpochSchedule" => Ok(SysvarTy::EpochSchedule),
+        "Fees" => Ok(SysvarTy::Fees),
+        "RecentBlockhashes" => Ok(SysvarTy::RecentBlockhashes),
+        "SlotHashes" => Ok(SysvarTy::SlotHashes),
+        "SlotHistory" => Ok(SysvarTy::SlotHistory),
+        "StakeHistory" => Ok(SysvarTy::StakeHistory),
+        "Instructions" => Ok(SysvarTy::Instructions),
+        "Rewards" => Ok(SysvarTy::Rewards),
+        _ => Err(syn::Error::new_spanned(
+            &account_ident,
+            format!("invalid sysvar `{}`", account_ident),
+        )),
+    }
+}
+
+/// Consumes the next token, asserting that it's the given punctuation
+/// character. `prev` is used to anchor the "expected X after this" span
+/// when the token stream runs out early.
+fn expect_punct(
+    tts: &mut proc_macro2::token_stream::IntoIter,
+    c: char,
+    prev: &impl quote::ToTokens,
+) -> ParseResult<()> {
+    match tts.next() {
+        Some(proc_macro2::TokenTree::Punct(punct)) if punct.as_char() == c => Ok(()),
+        Some(tt) => Err(syn::Error::new_spanned(tt, format!("expected `{}`", c))),
+        None => Err(syn::Error::new_spanned(
+            prev,
+            format!("expected `{}` after this", c),
+        )),
+    }
+}
+
+/// Decodes a base58-encoded pubkey string literal (e.g. a program ID) into
+/// its raw 32-byte representation, as produced by `bs58::encode`.
+fn parse_base58_pubkey(literal: &proc_macro2::Literal) -> ParseResult<[u8; 32]> {
+    let lit_str = match syn::Lit::new(literal.clone()) {
+        syn::Lit::Str(lit_str) => lit_str,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                literal,
+                "expected a string literal containing a base58 pubkey",
+            ))
+        }
+    };
+    let bytes = bs58::decode(lit_str.value())
+        .into_vec()
+        .map_err(|_| syn::Error::new_spanned(&lit_str, "invalid base58 pubkey"))?;
+    if bytes.len() != 32 {
+        return Err(syn::Error::new_spanned(
+            &lit_str,
+            format!("invalid pubkey: expected 32 bytes, decoded {}", bytes.len()),
+        ));
+    }
+    let mut address = [0u8; 32];
+    address.copy_from_slice(&bytes);
+    Ok(address)
+}
+
+/// The token after `=` in an `owner`/`address` constraint: either another
+/// field in the accounts struct, or a base58 pubkey literal baked in at
+/// compile time.
+enum FieldOrPubkey {
+    Field(syn::Ident),
+    Pubkey([u8; 32]),
+}
+
+fn parse_field_or_pubkey(
+    tts: &mut proc_macro2::token_stream::IntoIter,
+    prev: &impl quote::ToTokens,
+) -> ParseResult<FieldOrPubkey> {
+    match tts.next() {
+        Some(proc_macro2::TokenTree::Ident(target)) => Ok(FieldOrPubkey::Field(target)),
+        Some(proc_macro2::TokenTree::Literal(literal)) => {
+            Ok(FieldOrPubkey::Pubkey(parse_base58_pubkey(&literal)?))
+        }
+        Some(tt) => Err(syn::Error::new_spanned(
+            tt,
+            "expected an identifier or a base58 pubkey literal",
+        )),
+        None => Err(syn::Error::new_spanned(
+            prev,
+            "expected an identifier or a base58 pubkey literal after this",
+        )),
+    }
+}
+
+/// Collects tokens up to the next top-level comma (i.e. not inside a
+/// generic argument list, since parenthesized/bracketed/braced groups are
+/// already atomic `TokenTree::Group`s) and parses them as a `syn::Expr`.
+/// This lets constraints take arbitrary expressions, e.g.
+/// `space = 8 + size_of::<Foo>()`, without round-tripping through a
+/// quoted string literal.
+///
+/// Only `::<...>` turbofish opens bracket-depth tracking, so a bare `<` or
+/// `>` comparison (e.g. `constraint = a < b`) is left alone and doesn't
+/// mask a top-level comma.
+fn parse_expr_until_comma(
+    tts: &mut proc_macro2::token_stream::IntoIter,
+    prev: &impl quote::ToTokens,
+) -> ParseResult<proc_macro2::TokenStream> {
+    let mut depth: i32 = 0;
+    let mut colon_run = 0u8;
+    let mut tokens = proc_macro2::TokenStream::new();
+    loop {
+        let mut peek = tts.clone();
+        match peek.next() {
+            None => break,
+            Some(proc_macro2::TokenTree::Punct(p)) if p.as_char() == ',' && depth == 0 => break,
+            Some(tt) => {
+                match &tt {
+                    proc_macro2::TokenTree::Punct(p) if p.as_char() == ':' => {
+                        colon_run += 1;
+                    }
+                    proc_macro2::TokenTree::Punct(p) if p.as_char() == '<' => {
+                        // Only a `::<` turbofish (or a `<` nested inside one)
+                        // opens bracket-depth tracking; a bare relational `<`
+                        // is left alone.
+                        if colon_run >= 2 || depth > 0 {
+                            depth += 1;
+                        }
+                        colon_run = 0;
+                    }
+                    proc_macro2::TokenTree::Punct(p) if p.as_char() == '>' && depth > 0 => {
+                        depth -= 1;
+                        colon_run = 0;
+                    }
+                    _ => {
+                        colon_run = 0;
+                    }
+                }
+                tokens.extend(std::iter::once(tt));
+                tts.next();
+            }
+        }
+    }
+    if tokens.is_empty() {
+        return Err(syn::Error::new_spanned(
+            prev,
+            "expected an expression after this",
+        ));
+    }
+    syn::parse2::<syn::Expr>(tokens.clone())
+        .map_err(|e| syn::Error::new_spanned(tokens.clone(), e.to_string()))?;
+    Ok(tokens)
+}
+
+/// Consumes the next token, asserting that it's an identifier.
+fn expect_ident(
+    tts: &mut proc_macro2::token_stream::IntoIter,
+    prev: &impl quote::ToTokens,
+) -> ParseResult<syn::Ident> {
+    match tts.next() {
+        Some(proc_macro2::TokenTree::Ident(ident)) => Ok(ident),
+        Some(tt) => Err(syn::Error::new_spanned(tt, "expected an identifier")),
+        None => Err(syn::Error::new_spanned(
+            prev,
+            "expected an identifier after this",
+        )),
     }
 }
 
 fn parse_constraints(
     anchor: &syn::Attribute,
-) -> (
+) -> ParseResult<(
     Vec<Constraint>,
     bool,
     bool,
@@ -193,11 +391,22 @@ fn parse_constraints(
     Option<syn::Ident>,
     Option<proc_macro2::TokenStream>,
     Vec<syn::Ident>,
-) {
+)> {
     let mut tts = anchor.tokens.clone().into_iter();
-    let g_stream = match tts.next().expect("Must have a token group") {
-        proc_macro2::TokenTree::Group(g) => g.stream(),
-        _ => panic!("Invalid syntax"),
+    let g_stream = match tts.next() {
+        Some(proc_macro2::TokenTree::Group(g)) => g.stream(),
+        Some(tt) => {
+            return Err(syn::Error::new_spanned(
+                tt,
+                "expected a parenthesized list of constraints",
+            ))
+        }
+        None => {
+            return Err(syn::Error::new_spanned(
+                anchor,
+                "expected a parenthesized list of constraints after this",
+            ))
+        }
     };
 
     let mut is_init = false;
@@ -231,67 +440,62 @@ fn parse_constraints(
                     constraints.push(Constraint::Signer(ConstraintSigner {}));
                 }
                 "seeds" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let seeds = match inner_tts.next() {
+                        Some(proc_macro2::TokenTree::Group(g)) => g,
+                        Some(tt) => {
+                            return Err(syn::Error::new_spanned(
+                                tt,
+                                "expected a bracketed list of seeds",
+                            ))
+                        }
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                ident,
+                                "expected a bracketed list of seeds after this",
+                            ))
                         }
-                        _ => panic!("invalid syntax"),
-                    };
-                    let seeds = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Group(g) => g,
-                        _ => panic!("invalid syntax"),
                     };
                     constraints.push(Constraint::Seeds(ConstraintSeeds { seeds }))
                 }
                 "belongs_to" | "has_one" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    let join_target = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
-                    };
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let join_target = expect_ident(&mut inner_tts, &ident)?;
                     constraints.push(Constraint::BelongsTo(ConstraintBelongsTo { join_target }))
                 }
                 "owner" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let owner = match parse_field_or_pubkey(&mut inner_tts, &ident)? {
+                        FieldOrPubkey::Field(target) => ConstraintOwner::Field(target),
+                        FieldOrPubkey::Pubkey(address) => ConstraintOwner::Address(address),
                     };
-                    let owner_target = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
+                    constraints.push(Constraint::Owner(owner));
+                }
+                "address" => {
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let address = match parse_field_or_pubkey(&mut inner_tts, &ident)? {
+                        FieldOrPubkey::Field(target) => ConstraintAddress::Field(target),
+                        FieldOrPubkey::Pubkey(address) => ConstraintAddress::Literal(address),
                     };
-                    constraints.push(Constraint::Owner(ConstraintOwner { owner_target }));
+                    constraints.push(Constraint::Address(address));
                 }
                 "rent_exempt" => {
                     match inner_tts.next() {
                         None => is_rent_exempt = Some(true),
                         Some(tkn) => {
                             match tkn {
-                                proc_macro2::TokenTree::Punct(punct) => {
-                                    assert!(punct.as_char() == '=');
-                                    punct
-                                }
-                                _ => panic!("invalid syntax"),
-                            };
-                            let should_skip = match inner_tts.next().unwrap() {
-                                proc_macro2::TokenTree::Ident(ident) => ident,
-                                _ => panic!("invalid syntax"),
+                                proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '=' => {}
+                                tkn => return Err(syn::Error::new_spanned(tkn, "expected `=`")),
                             };
+                            let should_skip = expect_ident(&mut inner_tts, &ident)?;
                             match should_skip.to_string().as_str() {
                                 "skip" => {
                                     is_rent_exempt = Some(false);
-                                },
-                                _ => panic!("invalid syntax: omit the rent_exempt attribute to enforce rent exemption"),
+                                }
+                                _ => return Err(syn::Error::new_spanned(
+                                    &should_skip,
+                                    "omit the `rent_exempt` attribute to enforce rent exemption",
+                                )),
                             };
                         }
                     };
@@ -300,97 +504,53 @@ fn parse_constraints(
                     constraints.push(Constraint::Executable(ConstraintExecutable {}));
                 }
                 "state" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    let program_target = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
-                    };
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let program_target = expect_ident(&mut inner_tts, &ident)?;
                     constraints.push(Constraint::State(ConstraintState { program_target }));
                 }
                 "associated" => {
                     is_associated = true;
                     is_mut = true;
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    let associated_target = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
-                    };
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let associated_target = expect_ident(&mut inner_tts, &ident)?;
                     constraints.push(Constraint::Associated(ConstraintAssociated {
                         associated_target,
                     }));
                 }
                 "with" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    associated_seeds.push(match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
-                    });
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    associated_seeds.push(expect_ident(&mut inner_tts, &ident)?);
                 }
                 "payer" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    let _payer = match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Ident(ident) => ident,
-                        _ => panic!("invalid syntax"),
-                    };
-                    payer = Some(_payer);
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    payer = Some(expect_ident(&mut inner_tts, &ident)?);
                 }
                 "space" => {
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Punct(punct) => {
-                            assert!(punct.as_char() == '=');
-                            punct
-                        }
-                        _ => panic!("invalid syntax"),
-                    };
-                    match inner_tts.next().unwrap() {
-                        proc_macro2::TokenTree::Literal(literal) => {
-                            let tokens: proc_macro2::TokenStream =
-                                literal.to_string().replace("\"", "").parse().unwrap();
-                            space = Some(tokens);
-                        }
-                        _ => panic!("invalid space"),
-                    }
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    space = Some(parse_expr_until_comma(&mut inner_tts, &ident)?);
+                }
+                "constraint" => {
+                    expect_punct(&mut inner_tts, '=', &ident)?;
+                    let tokens = parse_expr_until_comma(&mut inner_tts, &ident)?;
+                    constraints.push(Constraint::Literal(ConstraintLiteral { tokens }));
                 }
                 _ => {
-                    panic!("invalid syntax");
+                    return Err(syn::Error::new_spanned(
+                        &ident,
+                        format!("unknown constraint `{}`", ident),
+                    ));
                 }
             },
             proc_macro2::TokenTree::Punct(punct) => {
                 if punct.as_char() != ',' {
-                    panic!("invalid syntax");
+                    return Err(syn::Error::new_spanned(
+                        punct,
+                        "expected a `,` separating constraints",
+                    ));
                 }
             }
-            proc_macro2::TokenTree::Literal(literal) => {
-                let tokens: proc_macro2::TokenStream =
-                    literal.to_string().replace("\"", "").parse().unwrap();
-                constraints.push(Constraint::Literal(ConstraintLiteral { tokens }));
-            }
-            _ => {
-                panic!("invalid syntax");
+            tt => {
+                return Err(syn::Error::new_spanned(tt, "invalid constraint syntax"));
             }
         }
     }
@@ -407,7 +567,7 @@ fn parse_constraints(
         }
     }
 
-    (
+    Ok((
         constraints,
         is_mut,
         is_signer,
@@ -415,5 +575,184 @@ fn parse_constraints(
         payer,
         space,
         associated_seeds,
-    )
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::{quote, ToTokens};
+
+    fn field(tokens: proc_macro2::TokenStream) -> syn::Field {
+        let strct: syn::ItemStruct = syn::parse_quote! {
+            struct S { #tokens }
+        };
+        match strct.fields {
+            syn::Fields::Named(fields) => fields.named.into_iter().next().unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn base58_pubkey_decodes_valid_literal() {
+        let pubkey = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+        let literal = proc_macro2::Literal::string(pubkey);
+        let decoded = parse_base58_pubkey(&literal).expect("should decode");
+        assert_eq!(decoded.to_vec(), bs58::decode(pubkey).into_vec().unwrap());
+    }
+
+    #[test]
+    fn base58_pubkey_rejects_invalid_alphabet() {
+        // '0' is not part of the base58 alphabet.
+        let literal = proc_macro2::Literal::string("0000000000000000000000000000000000000000");
+        assert!(parse_base58_pubkey(&literal).is_err());
+    }
+
+    #[test]
+    fn base58_pubkey_rejects_wrong_length() {
+        // Valid base58, but decodes to fewer than 32 bytes.
+        let literal = proc_macro2::Literal::string("3QJmV3");
+        let err = parse_base58_pubkey(&literal).unwrap_err();
+        assert!(err.to_string().contains("expected 32 bytes"));
+    }
+
+    #[test]
+    fn base58_pubkey_rejects_non_string_literal() {
+        let literal = proc_macro2::Literal::u8_suffixed(0);
+        let err = parse_base58_pubkey(&literal).unwrap_err();
+        assert!(err.to_string().contains("expected a string literal"));
+    }
+
+    #[test]
+    fn expr_until_comma_stops_at_top_level_comma() {
+        let tokens = quote! { 8 + size_of::<Foo>(), mut };
+        let mut iter = tokens.into_iter();
+        let prev = quote! { space };
+        let expr = parse_expr_until_comma(&mut iter, &prev).unwrap();
+        assert_eq!(expr.to_string(), quote! { 8 + size_of :: < Foo > ( ) }.to_string());
+        // The terminating comma itself is left in the stream for the caller
+        // (`parse_constraints`'s outer loop) to consume.
+        match iter.next() {
+            Some(proc_macro2::TokenTree::Punct(p)) => assert_eq!(p.as_char(), ','),
+            other => panic!("expected a trailing `,`, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn expr_until_comma_treats_bare_relational_operators_as_non_bracketing() {
+        let tokens = quote! { a < b, mut };
+        let mut iter = tokens.into_iter();
+        let prev = quote! { constraint };
+        let expr = parse_expr_until_comma(&mut iter, &prev).unwrap();
+        assert_eq!(expr.to_string(), quote! { a < b }.to_string());
+        iter.next(); // the terminating comma
+        match iter.next() {
+            Some(proc_macro2::TokenTree::Ident(ident)) => assert_eq!(ident, "mut"),
+            other => panic!("expected `mut` to remain, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn expr_until_comma_does_not_split_on_commas_inside_turbofish() {
+        let tokens = quote! { Vec::<Foo, Bar>::new(), mut };
+        let mut iter = tokens.into_iter();
+        let prev = quote! { constraint };
+        let expr = parse_expr_until_comma(&mut iter, &prev).unwrap();
+        assert_eq!(
+            expr.to_string(),
+            quote! { Vec :: < Foo , Bar > :: new ( ) }.to_string()
+        );
+        iter.next(); // the terminating comma
+        match iter.next() {
+            Some(proc_macro2::TokenTree::Ident(ident)) => assert_eq!(ident, "mut"),
+            other => panic!("expected `mut` to remain, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn expr_until_comma_rejects_empty_expression() {
+        let tokens = quote! { , mut };
+        let mut iter = tokens.into_iter();
+        let prev = quote! { constraint };
+        assert!(parse_expr_until_comma(&mut iter, &prev).is_err());
+    }
+
+    #[test]
+    fn ident_string_resolves_fully_qualified_wrapper_type() {
+        let f = field(quote! { my_account: anchor_lang::ProgramAccount<'info, crate::state::MyAccount> });
+        assert_eq!(ident_string(&f).unwrap(), "ProgramAccount");
+    }
+
+    #[test]
+    fn parse_ty_preserves_segmented_inner_account_path() {
+        let f = field(quote! { my_account: ProgramAccount<'info, crate::state::MyAccount> });
+        let ty = parse_ty(&f).unwrap();
+        match ty {
+            Ty::ProgramAccount(ty) => {
+                assert_eq!(
+                    ty.account_ident.to_token_stream().to_string(),
+                    quote! { crate::state::MyAccount }.to_string()
+                );
+            }
+            other => panic!("expected Ty::ProgramAccount, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_sysvar_resolves_segmented_sysvar_path() {
+        let f = field(quote! { clock: Sysvar<'info, sysvar::clock::Clock> });
+        let ty = parse_ty(&f).unwrap();
+        assert!(matches!(ty, Ty::Sysvar(SysvarTy::Clock)));
+    }
+
+    #[test]
+    fn parse_rejects_struct_without_named_fields() {
+        let strct: syn::ItemStruct = syn::parse_quote! { struct S(u8); };
+        let err = parse(&strct).unwrap_err();
+        assert!(err.to_string().contains("named fields"));
+    }
+
+    #[test]
+    fn parse_account_attr_rejects_duplicate_account_attrs() {
+        let f = field(quote! { #[account(mut)] #[account(signer)] my_account: AccountInfo<'info> });
+        let err = parse_account_attr(&f).unwrap_err();
+        assert!(err.to_string().contains("one account attribute"));
+    }
+
+    #[test]
+    fn parse_field_rejects_unknown_constraint() {
+        let f = field(quote! { #[account(bogus)] my_account: AccountInfo<'info> });
+        let err = parse_account_field(&f).unwrap_err();
+        assert!(err.to_string().contains("unknown constraint"));
+    }
+
+    #[test]
+    fn parse_constraints_requires_equals_after_owner() {
+        let f = field(quote! { #[account(owner)] my_account: AccountInfo<'info> });
+        let err = parse_account_field(&f).unwrap_err();
+        assert!(err.to_string().contains("expected `=`"));
+    }
+
+    #[test]
+    fn parse_account_rejects_wrong_generic_arity() {
+        let f = field(quote! { my_account: ProgramAccount<'info> });
+        let err = parse_ty(&f).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected angle brackets with a lifetime and a single type"));
+    }
+
+    #[test]
+    fn parse_sysvar_rejects_unknown_sysvar_name() {
+        let f = field(quote! { clock: Sysvar<'info, Bogus> });
+        let err = parse_ty(&f).unwrap_err();
+        assert!(err.to_string().contains("invalid sysvar"));
+    }
+
+    #[test]
+    fn parse_ty_rejects_non_path_type() {
+        let f = field(quote! { my_field: [u8; 32] });
+        let err = parse_ty(&f).unwrap_err();
+        assert!(err.to_string().contains("invalid account"));
+    }
 }